@@ -0,0 +1,132 @@
+//! A small fzf-style fuzzy subsequence matcher used to rank and highlight
+//! `Options` candidates as the user types into a filter.
+
+const SCORE_MATCH: i64 = 16;
+const SCORE_CONSECUTIVE_BONUS: i64 = 8;
+const SCORE_BOUNDARY_BONUS: i64 = 10;
+const SCORE_GAP_PENALTY: i64 = 1;
+
+fn is_boundary(chars: &[char], index: usize) -> bool {
+    if index == 0 {
+        return true;
+    }
+
+    let previous = chars[index - 1];
+    let current = chars[index];
+
+    matches!(previous, '/' | '_' | '-' | ' ') || (previous.is_lowercase() && current.is_uppercase())
+}
+
+/// Tests whether `query` is a case-insensitive subsequence of `candidate`.
+/// If it is, returns a score (higher is better) and the indices of the
+/// matched characters within `candidate`.
+pub(crate) fn fuzzy_match(query: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let candidate_chars: Vec<char> = candidate.chars().collect();
+
+    let mut positions = Vec::with_capacity(query.len());
+    let mut score = 0_i64;
+    let mut query_index = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (candidate_index, &c) in candidate_chars.iter().enumerate() {
+        if query_index == query.len() {
+            break;
+        }
+
+        // Lower-cased one character at a time (rather than lower-casing the
+        // whole candidate up front) so this index always lines up with
+        // `candidate_chars`, even for characters like `'İ'` that expand to
+        // more than one char under full-string lowercasing.
+        let Some(lower) = c.to_lowercase().next() else {
+            continue;
+        };
+        if lower != query[query_index] {
+            continue;
+        }
+
+        score += SCORE_MATCH;
+
+        if let Some(last) = last_match {
+            let gap = candidate_index - last - 1;
+            if gap == 0 {
+                score += SCORE_CONSECUTIVE_BONUS;
+            } else {
+                score -= gap as i64 * SCORE_GAP_PENALTY;
+            }
+        }
+
+        if is_boundary(&candidate_chars, candidate_index) {
+            score += SCORE_BOUNDARY_BONUS;
+        }
+
+        positions.push(candidate_index);
+        last_match = Some(candidate_index);
+        query_index += 1;
+    }
+
+    if query_index < query.len() {
+        return None;
+    }
+
+    Some((score, positions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_subsequence_case_insensitively() {
+        let (_, positions) = fuzzy_match("hlo", "Hello").unwrap();
+        assert_eq!(positions, vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn rejects_non_subsequence() {
+        assert!(fuzzy_match("xyz", "Hello").is_none());
+    }
+
+    #[test]
+    fn consecutive_matches_score_higher_than_scattered() {
+        // Same query length so the comparison isolates the consecutive bonus
+        // rather than the extra base score of a longer match.
+        let (consecutive, _) = fuzzy_match("hel", "hello").unwrap();
+        let (scattered, _) = fuzzy_match("hlo", "hello").unwrap();
+        assert!(consecutive > scattered);
+    }
+
+    #[test]
+    fn boundary_matches_score_higher() {
+        let (boundary, _) = fuzzy_match("f", "foo/bar").unwrap();
+        let (mid, _) = fuzzy_match("o", "foo/bar").unwrap();
+        assert!(boundary > mid);
+    }
+
+    #[test]
+    fn camel_case_hump_counts_as_boundary() {
+        let (score, positions) = fuzzy_match("b", "fooBar").unwrap();
+        assert_eq!(positions, vec![3]);
+        let (plain, _) = fuzzy_match("o", "fooBar").unwrap();
+        assert!(score > plain);
+    }
+
+    #[test]
+    fn empty_query_matches_everything_with_no_positions() {
+        let (score, positions) = fuzzy_match("", "anything").unwrap();
+        assert_eq!(score, 0);
+        assert!(positions.is_empty());
+    }
+
+    #[test]
+    fn does_not_panic_on_characters_that_expand_under_lowercasing() {
+        // 'İ' (U+0130) lower-cases to the two-char string "i̇", which used to
+        // desync the per-char index from `candidate_chars` and panic.
+        assert!(fuzzy_match("i", "Xİ").is_some());
+        assert!(fuzzy_match("xyz", "Xİ").is_none());
+    }
+}
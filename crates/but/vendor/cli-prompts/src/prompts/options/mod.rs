@@ -0,0 +1,114 @@
+mod fuzzy;
+
+pub(crate) use fuzzy::fuzzy_match;
+
+/// A single candidate that survived the current filter, together with the
+/// data needed to rank and highlight it.
+#[derive(Debug, Clone)]
+struct FilteredOption {
+    /// Index into `options`/`transformed_options`.
+    index: usize,
+    /// Higher is a better match. `0` for the unfiltered (empty query) case.
+    score: i64,
+    /// Indices into the transformed option's `chars()` that matched the query.
+    positions: Vec<usize>,
+}
+
+/// Holds the full set of options handed to a prompt, their string
+/// representation, and the subset that currently passes the filter, ranked
+/// best match first.
+pub struct Options<T> {
+    options: Vec<T>,
+    transformed_options: Vec<String>,
+    filtered: Vec<FilteredOption>,
+}
+
+impl<T> Options<T> {
+    pub fn from_iter<I>(options: I) -> Self
+    where
+        T: Into<String> + Clone,
+        I: Iterator<Item = T>,
+    {
+        Self::from_iter_transformed(options, |option: &T| option.clone().into())
+    }
+
+    pub fn from_iter_transformed<I, F>(options: I, transformation: F) -> Self
+    where
+        I: Iterator<Item = T>,
+        F: Fn(&T) -> String,
+    {
+        let options: Vec<T> = options.collect();
+        let transformed_options: Vec<String> = options.iter().map(transformation).collect();
+        let filtered = (0..options.len())
+            .map(|index| FilteredOption {
+                index,
+                score: 0,
+                positions: Vec::new(),
+            })
+            .collect();
+
+        Self {
+            options,
+            transformed_options,
+            filtered,
+        }
+    }
+
+    /// Re-rank `filtered_options()` against `query` using fuzzy subsequence
+    /// matching. Non-matching options are dropped; the rest are sorted by
+    /// descending score, ties broken by shorter candidate length.
+    pub fn filter(&mut self, query: &str) {
+        if query.is_empty() {
+            self.filtered = (0..self.options.len())
+                .map(|index| FilteredOption {
+                    index,
+                    score: 0,
+                    positions: Vec::new(),
+                })
+                .collect();
+            return;
+        }
+
+        let mut filtered: Vec<FilteredOption> = self
+            .transformed_options
+            .iter()
+            .enumerate()
+            .filter_map(|(index, candidate)| {
+                fuzzy_match(query, candidate).map(|(score, positions)| FilteredOption {
+                    index,
+                    score,
+                    positions,
+                })
+            })
+            .collect();
+
+        filtered.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| self.transformed_options[a.index].len().cmp(&self.transformed_options[b.index].len()))
+        });
+
+        self.filtered = filtered;
+    }
+
+    pub fn filtered_options(&self) -> Vec<usize> {
+        self.filtered.iter().map(|f| f.index).collect()
+    }
+
+    /// Character indices within the filtered option at `filtered_index` that
+    /// matched the current query, for highlighting in `draw_option`.
+    pub fn match_positions(&self, filtered_index: usize) -> &[usize] {
+        self.filtered
+            .get(filtered_index)
+            .map(|f| f.positions.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn transformed_options(&self) -> &[String] {
+        &self.transformed_options
+    }
+
+    pub fn all_options_mut(&mut self) -> &mut Vec<T> {
+        &mut self.options
+    }
+}
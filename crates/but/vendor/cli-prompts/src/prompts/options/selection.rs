@@ -132,19 +132,46 @@ impl<T> MultiOptionPrompt<T> for Selection<T> {
 
     fn draw_option(
         &self,
-        _: usize,
+        filtered_index: usize,
         option_label: &str,
         is_selected: bool,
         cmd_buffer: &mut impl CommandBuffer,
     ) {
         if is_selected {
             self.style.selected_marker.print(cmd_buffer);
-            self.style
-                .selected_option_formatting
-                .print(option_label, cmd_buffer);
         } else {
             self.style.not_selected_marker.print(cmd_buffer);
-            self.style.option_formatting.print(option_label, cmd_buffer)
+        }
+
+        let formatting = if is_selected {
+            &self.style.selected_option_formatting
+        } else {
+            &self.style.option_formatting
+        };
+
+        let matches = self.options.match_positions(filtered_index);
+        if matches.is_empty() {
+            formatting.print(option_label, cmd_buffer);
+            return;
+        }
+
+        // Matched characters are drawn with the opposite formatting of the
+        // rest of the label so they stand out regardless of whether this row
+        // happens to be the current selection.
+        let highlight = if is_selected {
+            &self.style.option_formatting
+        } else {
+            &self.style.selected_option_formatting
+        };
+
+        for (index, character) in option_label.chars().enumerate() {
+            let mut buf = [0; 4];
+            let piece = character.encode_utf8(&mut buf);
+            if matches.contains(&index) {
+                highlight.print(piece, cmd_buffer);
+            } else {
+                formatting.print(piece, cmd_buffer);
+            }
         }
     }
 
@@ -273,4 +300,30 @@ mod tests {
         ));
         assert_eq!(prompt.current_selection, 0);
     }
+
+    #[test]
+    fn typing_ranks_best_fuzzy_match_first() {
+        let mut prompt = Selection::new(
+            "Pick one",
+            ["src/main.rs", "settings.rs", "lib.rs"].into_iter(),
+        );
+
+        for c in "src".chars() {
+            prompt.on_key_pressed(Key::Char(c));
+        }
+
+        let top = prompt.options.filtered_options()[0];
+        assert_eq!(prompt.options.transformed_options()[top], "src/main.rs");
+    }
+
+    #[test]
+    fn typing_resets_selection_to_top() {
+        let mut prompt = Selection::new("Pick one", ["apple", "banana", "avocado"].into_iter());
+        prompt.on_key_pressed(Key::Char('a'));
+        prompt.current_selection = 2;
+
+        prompt.on_key_pressed(Key::Char('v'));
+
+        assert_eq!(prompt.current_selection, 0);
+    }
 }
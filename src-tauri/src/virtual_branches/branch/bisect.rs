@@ -0,0 +1,256 @@
+use anyhow::{Context, Result};
+
+use super::Branch;
+
+/// How merge commits in the ancestry are handled while collecting the
+/// candidate range to bisect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Only follow first-parents, like `git log --first-parent`.
+    FirstParentOnly,
+    /// Walk every reachable commit topologically, merges included.
+    AllReachable,
+}
+
+/// What the caller found after checking out a probed commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Classification {
+    Good,
+    Bad,
+}
+
+/// Drives a binary search over a branch's `head` ancestry down to
+/// `merge_base` to find the first commit a caller classifies as bad.
+pub struct Bisect {
+    ancestry: Vec<git2::Oid>,
+    /// Index of the last commit known (or assumed) good, one before the
+    /// start of `ancestry` if nothing in it has been classified good yet.
+    lo: isize,
+    /// Index of the last commit known (or assumed) bad.
+    hi: isize,
+}
+
+impl Bisect {
+    /// Collects the linearized ancestry of `branch.head` down to
+    /// `merge_base` (exclusive) and starts a new bisection across it, oldest
+    /// commit first.
+    pub fn new(
+        repository: &git2::Repository,
+        branch: &Branch,
+        merge_base: git2::Oid,
+        merge_strategy: MergeStrategy,
+    ) -> Result<Self> {
+        let mut revwalk = repository.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+        if merge_strategy == MergeStrategy::FirstParentOnly {
+            revwalk.simplify_first_parent()?;
+        }
+        revwalk.push(branch.head)?;
+        if repository.find_commit(merge_base).is_ok() {
+            revwalk.hide(merge_base)?;
+        }
+
+        let ancestry = revwalk
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("failed to walk branch ancestry")?;
+        if ancestry.is_empty() {
+            anyhow::bail!("branch has no commits between merge base and head to bisect");
+        }
+
+        let hi = (ancestry.len() - 1) as isize;
+        Ok(Self {
+            ancestry,
+            lo: -1,
+            hi,
+        })
+    }
+
+    /// Whether the window has narrowed enough that `first_bad` can be
+    /// called.
+    pub fn is_done(&self) -> bool {
+        self.hi - self.lo <= 1
+    }
+
+    /// The commit to check out and classify next.
+    pub fn current(&self) -> git2::Oid {
+        let mid = self.lo + (self.hi - self.lo) / 2;
+        self.ancestry[mid as usize]
+    }
+
+    /// Narrows the search window given how `current()` was classified.
+    pub fn classify(&mut self, classification: Classification) {
+        let mid = self.lo + (self.hi - self.lo) / 2;
+        match classification {
+            Classification::Good => self.lo = mid,
+            Classification::Bad => self.hi = mid,
+        }
+    }
+
+    /// The first commit classified (or implied) bad, once `is_done()`.
+    pub fn first_bad(&self) -> git2::Oid {
+        self.ancestry[self.hi as usize]
+    }
+}
+
+/// Checks out each probed commit directly onto `repository`'s HEAD
+/// (mutating the caller's actual checkout for the duration of the
+/// bisection, rather than using a separate worktree), invokes `classify`
+/// with whatever state the caller observes, then restores both the
+/// original HEAD — re-attaching to the branch ref it started on, not
+/// just landing on the same commit detached — and any uncommitted
+/// ownership the probe stashed out of the way.
+pub fn run<F>(
+    repository: &mut git2::Repository,
+    branch: &Branch,
+    merge_base: git2::Oid,
+    merge_strategy: MergeStrategy,
+    mut classify: F,
+) -> Result<git2::Oid>
+where
+    F: FnMut(git2::Oid) -> Result<Classification>,
+{
+    let mut bisect = Bisect::new(repository, branch, merge_base, merge_strategy)?;
+
+    let original_head = capture_original_head(repository)?;
+    let stash = stash_uncommitted_ownership(repository)?;
+
+    let result = (|| {
+        while !bisect.is_done() {
+            let probe = bisect.current();
+            checkout_commit(repository, probe)?;
+            let classification = classify(probe)?;
+            bisect.classify(classification);
+        }
+        Ok(bisect.first_bad())
+    })();
+
+    restore_original_head(repository, &original_head)?;
+    restore_uncommitted_ownership(repository, stash)?;
+
+    result
+}
+
+/// The ref (or bare commit, if `repository` started out detached) that
+/// `run` found checked out before it began probing commits, so the
+/// repository can be put back the way it found it once bisection finishes.
+struct OriginalHead {
+    /// `refs/heads/...` name, if HEAD was attached to a branch.
+    branch_ref: Option<String>,
+    oid: Option<git2::Oid>,
+}
+
+fn capture_original_head(repository: &git2::Repository) -> Result<OriginalHead> {
+    let head = repository
+        .head()
+        .context("failed to read current HEAD before bisecting")?;
+
+    Ok(OriginalHead {
+        branch_ref: head
+            .name()
+            .filter(|name| name.starts_with("refs/heads/"))
+            .map(String::from),
+        oid: head.target(),
+    })
+}
+
+fn restore_original_head(repository: &git2::Repository, original: &OriginalHead) -> Result<()> {
+    let Some(oid) = original.oid else {
+        return Ok(());
+    };
+
+    let commit = repository.find_commit(oid)?;
+    repository.checkout_tree(commit.tree()?.as_object(), None)?;
+
+    match &original.branch_ref {
+        Some(branch_ref) => repository.set_head(branch_ref)?,
+        None => repository.set_head_detached(oid)?,
+    }
+
+    Ok(())
+}
+
+/// Opaque handle to whatever was stashed before a probe checkout, if
+/// anything.
+struct StashHandle(bool);
+
+fn stash_uncommitted_ownership(repository: &mut git2::Repository) -> Result<StashHandle> {
+    let is_empty = repository.statuses(None)?.is_empty();
+    if is_empty {
+        return Ok(StashHandle(false));
+    }
+
+    let signature = repository.signature()?;
+    repository.stash_save(&signature, "gitbutler bisect: uncommitted ownership", None)?;
+
+    Ok(StashHandle(true))
+}
+
+fn restore_uncommitted_ownership(repository: &mut git2::Repository, stash: StashHandle) -> Result<()> {
+    if !stash.0 {
+        return Ok(());
+    }
+
+    repository.stash_pop(0, None)?;
+
+    Ok(())
+}
+
+fn checkout_commit(repository: &git2::Repository, commit: git2::Oid) -> Result<()> {
+    let commit = repository.find_commit(commit)?;
+    repository.checkout_tree(commit.tree()?.as_object(), None)?;
+    repository.set_head_detached(commit.id())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oid(n: u8) -> git2::Oid {
+        git2::Oid::from_str(&format!("{:040x}", n)).unwrap()
+    }
+
+    fn bisect(len: usize) -> Bisect {
+        let ancestry = (0..len as u8).map(oid).collect::<Vec<_>>();
+        let hi = (ancestry.len() - 1) as isize;
+        Bisect {
+            ancestry,
+            lo: -1,
+            hi,
+        }
+    }
+
+    #[test]
+    fn test_finds_first_bad_at_the_very_start_of_ancestry() {
+        let mut bisect = bisect(4);
+        while !bisect.is_done() {
+            bisect.classify(Classification::Bad);
+        }
+        assert_eq!(bisect.first_bad(), oid(0));
+    }
+
+    #[test]
+    fn test_finds_first_bad_in_the_middle() {
+        // ancestry indices 0-1 are good, 2-3 are bad: first bad is index 2.
+        let mut bisect = bisect(4);
+        while !bisect.is_done() {
+            let probe = bisect.current();
+            let index = (0..4).find(|&i| oid(i) == probe).unwrap();
+            let classification = if index < 2 {
+                Classification::Good
+            } else {
+                Classification::Bad
+            };
+            bisect.classify(classification);
+        }
+        assert_eq!(bisect.first_bad(), oid(2));
+    }
+
+    #[test]
+    fn test_single_commit_ancestry_is_immediately_done() {
+        let bisect = bisect(1);
+        assert!(bisect.is_done());
+        assert_eq!(bisect.first_bad(), oid(0));
+    }
+}
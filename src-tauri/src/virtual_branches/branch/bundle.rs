@@ -0,0 +1,358 @@
+use std::io::{Read, Write};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{Branch, Writer as BranchWriter};
+
+/// Sidecar metadata shipped ahead of the packfile inside a branch bundle so
+/// the importer can reconstruct a `Branch` record without having seen the
+/// session it came from.
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleMetadata {
+    id: String,
+    name: String,
+    upstream: String,
+    ownership: String,
+}
+
+impl From<&Branch> for BundleMetadata {
+    fn from(branch: &Branch) -> Self {
+        Self {
+            id: branch.id.clone(),
+            name: branch.name.clone(),
+            upstream: branch.upstream.clone(),
+            ownership: branch
+                .ownership
+                .iter()
+                .map(|ownership| ownership.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+/// Whether the bundle's merge base was already present in the importing
+/// repository, letting the caller tell a bundle built against shared
+/// history the importer already has from one whose ancestry it is missing.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BaseAvailability {
+    Present,
+    Missing,
+}
+
+const BUNDLE_SIGNATURE: &str = "# v2 git bundle";
+
+/// Serializes `branch`'s `head` commit and its ancestry down to `merge_base`
+/// into a self-contained git bundle: a `BundleMetadata` header (its length
+/// as a little-endian `u64`, then the JSON), followed by a standard v2 git
+/// bundle (a `-merge_base` prerequisite line, ref list and packfile). Plain
+/// `git bundle verify`/`git fetch` cannot read the output as written; only
+/// the part after the metadata header is in git's bundle format.
+pub fn export_bundle(
+    repository: &git2::Repository,
+    branch: &Branch,
+    merge_base: git2::Oid,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let metadata_json = serde_json::to_vec(&BundleMetadata::from(branch))
+        .context("failed to serialize branch metadata")?;
+    out.write_all(&(metadata_json.len() as u64).to_le_bytes())
+        .context("failed to write branch bundle header")?;
+    out.write_all(&metadata_json)
+        .context("failed to write branch metadata")?;
+
+    let mut revwalk = repository.revwalk()?;
+    revwalk.push(branch.head)?;
+    if repository.find_commit(merge_base).is_ok() {
+        revwalk.hide(merge_base)?;
+    }
+
+    let mut packbuilder = repository.packbuilder()?;
+    for oid in revwalk {
+        packbuilder.insert_commit(oid?)?;
+    }
+
+    out.write_all(format!("{}\n", BUNDLE_SIGNATURE).as_bytes())?;
+    out.write_all(format!("-{}\n", merge_base).as_bytes())?;
+    out.write_all(format!("{} refs/heads/{}\n\n", branch.head, branch.name).as_bytes())?;
+
+    let mut write_error = None;
+    packbuilder
+        .foreach(|bytes| match out.write_all(bytes) {
+            Ok(()) => true,
+            Err(e) => {
+                write_error = Some(e);
+                false
+            }
+        })
+        .context("failed to write packfile to bundle")?;
+    if let Some(e) = write_error {
+        return Err(e).context("failed to write packfile to bundle");
+    }
+
+    Ok(())
+}
+
+/// Reads back a bundle produced by `export_bundle`: indexes the embedded
+/// packfile into `repository`'s object store, then reconstructs and persists
+/// the `Branch` record through `BranchWriter::write`. Returns whether the
+/// bundle's `-merge_base` prerequisite commit was already present, so a
+/// caller can tell a bundle built against shared history it's missing that
+/// ancestry rather than silently importing an incomplete branch.
+pub fn import_bundle(
+    repository: &git2::Repository,
+    writer: &BranchWriter,
+    bundle: &mut dyn Read,
+) -> Result<(Branch, BaseAvailability)> {
+    let mut len_bytes = [0; 8];
+    bundle
+        .read_exact(&mut len_bytes)
+        .context("bundle is missing its metadata header")?;
+    let metadata_len = u64::from_le_bytes(len_bytes) as usize;
+
+    let mut metadata_json = vec![0; metadata_len];
+    bundle.read_exact(&mut metadata_json)?;
+    let metadata: BundleMetadata =
+        serde_json::from_slice(&metadata_json).context("invalid branch bundle metadata")?;
+
+    let mut header = Vec::new();
+    let mut byte = [0; 1];
+    loop {
+        bundle.read_exact(&mut byte)?;
+        header.push(byte[0]);
+        if header.ends_with(b"\n\n") {
+            break;
+        }
+    }
+    let header = String::from_utf8(header).context("bundle header is not valid utf8")?;
+    let mut lines = header.lines();
+    if lines.next() != Some(BUNDLE_SIGNATURE) {
+        bail!("not a v2 git bundle");
+    }
+    let prerequisite_line = lines
+        .next()
+        .context("bundle is missing its merge-base prerequisite line")?;
+    let merge_base = prerequisite_line
+        .strip_prefix('-')
+        .context("malformed bundle prerequisite line")?;
+    let merge_base = git2::Oid::from_str(merge_base)?;
+
+    let ref_line = lines.next().context("bundle is missing a ref line")?;
+    let head = ref_line
+        .split_whitespace()
+        .next()
+        .context("malformed bundle ref line")?;
+    let head = git2::Oid::from_str(head)?;
+
+    let base_availability = if repository.find_commit(merge_base).is_ok() {
+        BaseAvailability::Present
+    } else {
+        BaseAvailability::Missing
+    };
+
+    let mut pack_data = Vec::new();
+    bundle.read_to_end(&mut pack_data)?;
+    if !pack_data.is_empty() {
+        let odb = repository.odb()?;
+        let mut packwriter = odb.packwriter()?;
+        packwriter.write_all(&pack_data)?;
+        packwriter.commit()?;
+    }
+
+    let head_commit = repository.find_commit(head)?;
+
+    let ownership = metadata
+        .ownership
+        .lines()
+        .map(super::Ownership::parse_string)
+        .collect::<Result<Vec<_>>>()
+        .context("invalid ownership in branch bundle")?;
+
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+
+    let branch = Branch {
+        id: metadata.id,
+        name: metadata.name,
+        applied: false,
+        upstream: metadata.upstream,
+        created_timestamp_ms: now_ms,
+        updated_timestamp_ms: now_ms,
+        tree: head_commit.tree_id(),
+        head,
+        ownership,
+        signed_by: None,
+        head_signature_verified: None,
+    };
+
+    if writer.exists(&branch.id) {
+        bail!(
+            "a branch with id {} already exists in the destination repository",
+            branch.id
+        );
+    }
+
+    writer.write(&branch)?;
+
+    Ok((branch, base_availability))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use crate::{gb_repository, projects, storage, users};
+
+    use super::*;
+
+    /// A two-commit history: an empty-tree `merge_base` commit, and a `head`
+    /// commit on top of it, also with an empty tree.
+    fn test_repository_with_history() -> Result<(git2::Repository, git2::Oid, git2::Oid)> {
+        let path = tempdir()?.path().to_str().unwrap().to_string();
+        let repository = git2::Repository::init(path)?;
+        let signature = git2::Signature::now("test", "test@email.com")?;
+        let tree = repository.find_tree(repository.treebuilder(None)?.write()?)?;
+
+        let merge_base = repository.commit(None, &signature, &signature, "merge base", &tree, &[])?;
+        let merge_base_commit = repository.find_commit(merge_base)?;
+        let head = repository.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "head",
+            &tree,
+            &[&merge_base_commit],
+        )?;
+
+        Ok((repository, merge_base, head))
+    }
+
+    fn test_writer(git_repository: &git2::Repository) -> Result<gb_repository::Repository> {
+        let project = projects::Project::try_from(git_repository)?;
+        let gb_repo_path = tempdir()?.path().to_str().unwrap().to_string();
+        let storage = storage::Storage::from_path(tempdir()?.path());
+        let user_store = users::Storage::new(storage.clone());
+        let project_store = projects::Storage::new(storage);
+        project_store.add_project(&project)?;
+        Ok(gb_repository::Repository::open(
+            gb_repo_path,
+            project.id,
+            project_store,
+            user_store,
+        )?)
+    }
+
+    fn copy_object(from: &git2::Repository, to: &git2::Repository, oid: git2::Oid) -> Result<()> {
+        let object = from.odb()?.read(oid)?;
+        to.odb()?.write(object.kind(), object.data())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_reports_missing_base_when_absent() -> Result<()> {
+        let (source, merge_base, head) = test_repository_with_history()?;
+        let branch = Branch {
+            id: "branch_1".to_string(),
+            name: "branch_name_1".to_string(),
+            applied: true,
+            upstream: "upstream_1".to_string(),
+            created_timestamp_ms: 1,
+            updated_timestamp_ms: 1,
+            tree: source.find_commit(head)?.tree_id(),
+            head,
+            ownership: vec![],
+            signed_by: None,
+            head_signature_verified: None,
+        };
+
+        let mut bundle = Vec::new();
+        export_bundle(&source, &branch, merge_base, &mut bundle)?;
+
+        let destination_dir = tempdir()?;
+        let destination = git2::Repository::init(destination_dir.path())?;
+        let gb_repo = test_writer(&destination)?;
+        let writer = BranchWriter::new(&gb_repo);
+
+        let (imported, base_availability) =
+            import_bundle(&destination, &writer, &mut Cursor::new(bundle))?;
+
+        assert_eq!(base_availability, BaseAvailability::Missing);
+        assert_eq!(imported.head, head);
+        assert!(destination.find_commit(head).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_refuses_to_clobber_an_existing_branch() -> Result<()> {
+        let (source, merge_base, head) = test_repository_with_history()?;
+        let branch = Branch {
+            id: "branch_1".to_string(),
+            name: "branch_name_1".to_string(),
+            applied: true,
+            upstream: "upstream_1".to_string(),
+            created_timestamp_ms: 1,
+            updated_timestamp_ms: 1,
+            tree: source.find_commit(head)?.tree_id(),
+            head,
+            ownership: vec![],
+            signed_by: None,
+            head_signature_verified: None,
+        };
+
+        let mut bundle = Vec::new();
+        export_bundle(&source, &branch, merge_base, &mut bundle)?;
+
+        let destination_dir = tempdir()?;
+        let destination = git2::Repository::init(destination_dir.path())?;
+        let gb_repo = test_writer(&destination)?;
+        let writer = BranchWriter::new(&gb_repo);
+
+        import_bundle(&destination, &writer, &mut Cursor::new(bundle.clone()))?;
+        assert!(import_bundle(&destination, &writer, &mut Cursor::new(bundle)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_reports_present_base_when_already_shared() -> Result<()> {
+        let (source, merge_base, head) = test_repository_with_history()?;
+        let branch = Branch {
+            id: "branch_2".to_string(),
+            name: "branch_name_2".to_string(),
+            applied: true,
+            upstream: "upstream_2".to_string(),
+            created_timestamp_ms: 1,
+            updated_timestamp_ms: 1,
+            tree: source.find_commit(head)?.tree_id(),
+            head,
+            ownership: vec![],
+            signed_by: None,
+            head_signature_verified: None,
+        };
+
+        let mut bundle = Vec::new();
+        export_bundle(&source, &branch, merge_base, &mut bundle)?;
+
+        let destination_dir = tempdir()?;
+        let destination = git2::Repository::init(destination_dir.path())?;
+        let merge_base_commit = source.find_commit(merge_base)?;
+        copy_object(&source, &destination, merge_base_commit.tree_id())?;
+        copy_object(&source, &destination, merge_base)?;
+
+        let gb_repo = test_writer(&destination)?;
+        let writer = BranchWriter::new(&gb_repo);
+
+        let (_, base_availability) = import_bundle(&destination, &writer, &mut Cursor::new(bundle))?;
+
+        assert_eq!(base_availability, BaseAvailability::Present);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{gb_repository, reader, writer};
+
+/// A single review comment against a branch, optionally anchored to a
+/// specific file/hunk of its `Ownership`, and optionally a reply in a
+/// thread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub id: String,
+    pub author: String,
+    pub timestamp_ms: u128,
+    pub file_path: Option<String>,
+    /// Hunk range this comment is anchored to, formatted the same way as an
+    /// `Ownership` hunk (e.g. `"12-18"`). `None` means the comment is about
+    /// the branch as a whole.
+    pub hunk: Option<String>,
+    pub body: String,
+    pub parent_id: Option<String>,
+    /// Set once the hunk this comment was anchored to no longer exists in
+    /// the branch's current `Ownership`, by `Branch::reconcile_comments`.
+    /// Outdated comments are kept, not dropped, so the thread stays intact.
+    pub outdated: bool,
+    /// Set by a reviewer through `CommentWriter::resolve` once the thread no
+    /// longer needs action. Independent of `outdated`: a branch-level
+    /// comment (no `file_path`/`hunk`) can never go outdated but can still
+    /// be resolved, and an outdated comment isn't necessarily resolved.
+    pub resolved: bool,
+}
+
+impl TryFrom<&dyn reader::Reader> for Comment {
+    type Error = reader::Error;
+
+    fn try_from(reader: &dyn reader::Reader) -> Result<Self, Self::Error> {
+        let id = reader.read_string("id")?;
+        let author = reader.read_string("author")?;
+        let timestamp_ms = reader.read_u128("timestamp_ms")?;
+        let body = reader.read_string("body")?;
+        let outdated = reader.read_bool("outdated").unwrap_or(false);
+        let resolved = reader.read_bool("resolved").unwrap_or(false);
+
+        let file_path = match reader.read_string("file_path") {
+            Ok(file_path) => Some(file_path),
+            Err(reader::Error::NotFound) => None,
+            Err(e) => return Err(e),
+        };
+        let hunk = match reader.read_string("hunk") {
+            Ok(hunk) => Some(hunk),
+            Err(reader::Error::NotFound) => None,
+            Err(e) => return Err(e),
+        };
+        let parent_id = match reader.read_string("parent_id") {
+            Ok(parent_id) => Some(parent_id),
+            Err(reader::Error::NotFound) => None,
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            id,
+            author,
+            timestamp_ms,
+            file_path,
+            hunk,
+            body,
+            parent_id,
+            outdated,
+            resolved,
+        })
+    }
+}
+
+/// A comment together with its replies, in timestamp order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommentThread {
+    pub comment: Comment,
+    pub replies: Vec<CommentThread>,
+}
+
+fn into_threads(mut comments: Vec<Comment>) -> Vec<CommentThread> {
+    comments.sort_by_key(|comment| comment.timestamp_ms);
+
+    fn children_of(parent_id: Option<&str>, comments: &[Comment]) -> Vec<CommentThread> {
+        comments
+            .iter()
+            .filter(|comment| comment.parent_id.as_deref() == parent_id)
+            .map(|comment| CommentThread {
+                comment: comment.clone(),
+                replies: children_of(Some(&comment.id), comments),
+            })
+            .collect()
+    }
+
+    children_of(None, &comments)
+}
+
+pub struct CommentReader<'reader> {
+    reader: &'reader dyn reader::Reader,
+}
+
+impl<'reader> CommentReader<'reader> {
+    pub fn new(reader: &'reader dyn reader::Reader) -> Self {
+        Self { reader }
+    }
+
+    /// Reads every comment under `branches/{branch_id}/comments/` and
+    /// returns the thread tree rooted at top-level comments, sorted by
+    /// timestamp.
+    pub fn read_comments(&self, branch_id: &str) -> Result<Vec<CommentThread>, reader::Error> {
+        let comments_path = format!("branches/{}/comments", branch_id);
+        if !self.reader.exists(&comments_path) {
+            return Ok(Vec::new());
+        }
+
+        let comments = self
+            .reader
+            .list_files(&comments_path)?
+            .into_iter()
+            .map(|comment_id| {
+                let single_reader: &dyn reader::Reader = &reader::SubReader::new(
+                    self.reader,
+                    &format!("{}/{}", comments_path, comment_id),
+                );
+                Comment::try_from(single_reader)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(into_threads(comments))
+    }
+}
+
+pub struct CommentWriter<'writer> {
+    repository: &'writer gb_repository::Repository,
+}
+
+impl<'writer> CommentWriter<'writer> {
+    pub fn new(repository: &'writer gb_repository::Repository) -> Self {
+        Self { repository }
+    }
+
+    /// Appends a new top-level comment or reply.
+    pub fn append(&self, branch_id: &str, comment: &Comment) -> anyhow::Result<()> {
+        self.write(branch_id, comment)
+    }
+
+    /// Overwrites a comment's body, leaving everything else (including its
+    /// place in the thread) untouched.
+    pub fn edit(&self, branch_id: &str, comment_id: &str, body: &str) -> anyhow::Result<()> {
+        let writer = writer::DirWriter::open(self.repository.root());
+        writer.write_string(
+            &format!("branches/{}/comments/{}/body", branch_id, comment_id),
+            body,
+        )?;
+        Ok(())
+    }
+
+    /// Marks a comment as resolved without removing it from the thread or
+    /// touching `outdated`, which only `Branch::reconcile_comments` sets.
+    pub fn resolve(&self, branch_id: &str, comment_id: &str) -> anyhow::Result<()> {
+        let writer = writer::DirWriter::open(self.repository.root());
+        writer.write_bool(
+            &format!("branches/{}/comments/{}/resolved", branch_id, comment_id),
+            true,
+        )?;
+        Ok(())
+    }
+
+    fn write(&self, branch_id: &str, comment: &Comment) -> anyhow::Result<()> {
+        let writer = writer::DirWriter::open(self.repository.root());
+        let path = format!("branches/{}/comments/{}", branch_id, comment.id);
+
+        writer.write_string(&format!("{}/id", path), &comment.id)?;
+        writer.write_string(&format!("{}/author", path), &comment.author)?;
+        writer.write_u128(&format!("{}/timestamp_ms", path), comment.timestamp_ms)?;
+        writer.write_string(&format!("{}/body", path), &comment.body)?;
+        writer.write_bool(&format!("{}/outdated", path), comment.outdated)?;
+        writer.write_bool(&format!("{}/resolved", path), comment.resolved)?;
+
+        match &comment.file_path {
+            Some(file_path) => writer.write_string(&format!("{}/file_path", path), file_path)?,
+            None => writer.remove(&format!("{}/file_path", path))?,
+        }
+        match &comment.hunk {
+            Some(hunk) => writer.write_string(&format!("{}/hunk", path), hunk)?,
+            None => writer.remove(&format!("{}/hunk", path))?,
+        }
+        match &comment.parent_id {
+            Some(parent_id) => writer.write_string(&format!("{}/parent_id", path), parent_id)?,
+            None => writer.remove(&format!("{}/parent_id", path))?,
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Branch, Ownership};
+    use super::*;
+
+    fn test_comment(id: &str, parent_id: Option<&str>, timestamp_ms: u128) -> Comment {
+        Comment {
+            id: id.to_string(),
+            author: "author".to_string(),
+            timestamp_ms,
+            file_path: None,
+            hunk: None,
+            body: "body".to_string(),
+            parent_id: parent_id.map(str::to_string),
+            outdated: false,
+            resolved: false,
+        }
+    }
+
+    #[test]
+    fn test_into_threads_nests_replies_under_their_parent() {
+        let comments = vec![
+            test_comment("reply", Some("root"), 2),
+            test_comment("root", None, 1),
+            test_comment("other_root", None, 3),
+        ];
+
+        let threads = into_threads(comments);
+
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0].comment.id, "root");
+        assert_eq!(threads[0].replies.len(), 1);
+        assert_eq!(threads[0].replies[0].comment.id, "reply");
+        assert_eq!(threads[1].comment.id, "other_root");
+        assert!(threads[1].replies.is_empty());
+    }
+
+    #[test]
+    fn test_reconcile_comments_flags_only_comments_whose_hunk_is_gone() {
+        let branch = Branch {
+            id: "branch_1".to_string(),
+            name: "branch_name_1".to_string(),
+            applied: true,
+            upstream: "upstream_1".to_string(),
+            created_timestamp_ms: 1,
+            updated_timestamp_ms: 1,
+            tree: git2::Oid::zero(),
+            head: git2::Oid::zero(),
+            ownership: vec![Ownership::parse_string("src/still_owned.rs:12-18").unwrap()],
+            signed_by: None,
+            head_signature_verified: None,
+        };
+
+        let mut still_owned = test_comment("still_owned", None, 1);
+        still_owned.file_path = Some("src/still_owned.rs".to_string());
+        still_owned.hunk = Some("12-18".to_string());
+
+        let mut hunk_gone = test_comment("hunk_gone", None, 2);
+        hunk_gone.file_path = Some("src/removed.rs".to_string());
+        hunk_gone.hunk = Some("1-5".to_string());
+
+        let mut branch_level = test_comment("branch_level", None, 3);
+        branch_level.resolved = true;
+
+        let reconciled = branch.reconcile_comments(vec![still_owned, hunk_gone, branch_level]);
+
+        assert!(!reconciled[0].outdated);
+        assert!(reconciled[1].outdated);
+        assert!(!reconciled[2].outdated);
+        assert!(reconciled[2].resolved, "resolve() shouldn't be undone by reconcile");
+    }
+}
@@ -1,9 +1,17 @@
+mod bisect;
+mod bundle;
+mod comment;
 mod hunk;
 mod ownership;
 mod reader;
+mod signing;
 mod writer;
 
+pub use bisect::{run as bisect, Bisect, Classification, MergeStrategy};
+pub use bundle::{import_bundle, export_bundle, BaseAvailability};
+pub use comment::{Comment, CommentReader, CommentThread, CommentWriter};
 pub use reader::BranchReader as Reader;
+pub use signing::{SignatureStatus, SigningBackend, SigningIdentity};
 pub use writer::BranchWriter as Writer;
 
 use serde::{Deserialize, Serialize};
@@ -23,6 +31,16 @@ pub struct Branch {
     pub tree: git2::Oid, // last git tree written to a session, or merge base tree if this is new. use this for delta calculation from the session data
     pub head: git2::Oid,
     pub ownership: Vec<Ownership>,
+    /// Key id of the `SigningIdentity` the `head` commit was signed with, if
+    /// the project is configured to sign virtual-branch commits.
+    pub signed_by: Option<String>,
+    /// Result of checking `head`'s signature against `signed_by`, populated
+    /// by `BranchReader::read` when it has repository access. `None` means
+    /// the signature wasn't checked, not that the commit is unsigned; a
+    /// verification attempt that itself failed (e.g. `gpg`/`ssh-keygen`
+    /// couldn't be spawned) is `Some(SignatureStatus::CheckFailed)`, not
+    /// `None`, so the two cases aren't conflated.
+    pub head_signature_verified: Option<SignatureStatus>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -75,6 +93,31 @@ impl Branch {
     pub fn contains(&self, ownership: &Ownership) -> bool {
         self.ownership.iter().any(|o| o.contains(ownership))
     }
+
+    /// Marks any comment whose `file_path`/`hunk` no longer matches an entry
+    /// in `self.ownership` as outdated, without dropping it. Call this after
+    /// `put`/`take` and persist the result with `CommentWriter`.
+    pub fn reconcile_comments(&self, mut comments: Vec<Comment>) -> Vec<Comment> {
+        for comment in &mut comments {
+            let (Some(file_path), Some(hunk)) = (&comment.file_path, &comment.hunk) else {
+                continue;
+            };
+
+            let still_owned = self.ownership.iter().any(|ownership| {
+                ownership.file_path.to_string_lossy() == *file_path
+                    && ownership
+                        .hunks
+                        .iter()
+                        .any(|owned_hunk| owned_hunk.to_string() == *hunk)
+            });
+
+            if !still_owned {
+                comment.outdated = true;
+            }
+        }
+
+        comments
+    }
 }
 
 impl TryFrom<&dyn crate::reader::Reader> for Branch {
@@ -147,6 +190,17 @@ impl TryFrom<&dyn crate::reader::Reader> for Branch {
                 ))
             })?;
 
+        let signed_by = match reader.read_string("meta/signed_by") {
+            Ok(signed_by) => Some(signed_by),
+            Err(crate::reader::Error::NotFound) => None,
+            Err(e) => {
+                return Err(crate::reader::Error::IOError(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    format!("meta/signed_by: {}", e),
+                )))
+            }
+        };
+
         Ok(Self {
             id,
             name,
@@ -157,6 +211,8 @@ impl TryFrom<&dyn crate::reader::Reader> for Branch {
             created_timestamp_ms,
             updated_timestamp_ms,
             ownership,
+            signed_by,
+            head_signature_verified: None,
         })
     }
 }
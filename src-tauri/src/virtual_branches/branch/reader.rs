@@ -1,14 +1,25 @@
 use crate::reader::{self, Reader, SubReader};
 
-use super::Branch;
+use super::{comment::CommentReader, signing, Branch, CommentThread};
 
 pub struct BranchReader<'reader> {
     reader: &'reader dyn reader::Reader,
+    repository: Option<&'reader git2::Repository>,
 }
 
 impl<'reader> BranchReader<'reader> {
     pub fn new(reader: &'reader dyn Reader) -> Self {
-        Self { reader }
+        Self {
+            reader,
+            repository: None,
+        }
+    }
+
+    /// Enables signature verification in `read`: without a repository,
+    /// `head_signature_verified` is always left as `None`.
+    pub fn with_repository(mut self, repository: &'reader git2::Repository) -> Self {
+        self.repository = Some(repository);
+        self
     }
 
     pub fn read_selected(&self) -> Result<Option<String>, reader::Error> {
@@ -26,7 +37,20 @@ impl<'reader> BranchReader<'reader> {
 
         let single_reader: &dyn crate::reader::Reader =
             &SubReader::new(self.reader, &format!("branches/{}", id));
-        Branch::try_from(single_reader)
+        let mut branch = Branch::try_from(single_reader)?;
+
+        if let Some(repository) = self.repository {
+            branch.head_signature_verified =
+                signing::verify_commit(repository, branch.head, branch.signed_by.as_deref()).ok();
+        }
+
+        Ok(branch)
+    }
+
+    /// Reads the comment thread attached to branch `id`, sorted by
+    /// timestamp.
+    pub fn read_comments(&self, id: &str) -> Result<Vec<CommentThread>, reader::Error> {
+        CommentReader::new(self.reader).read_comments(id)
     }
 }
 
@@ -68,6 +92,8 @@ mod tests {
                 file_path: format!("file/{}", unsafe { TEST_INDEX }).into(),
                 hunks: vec![],
             }],
+            signed_by: None,
+            head_signature_verified: None,
         }
     }
 
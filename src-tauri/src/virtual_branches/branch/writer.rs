@@ -0,0 +1,74 @@
+use anyhow::Result;
+
+use crate::{gb_repository, writer};
+
+use super::Branch;
+
+pub struct BranchWriter<'writer> {
+    repository: &'writer gb_repository::Repository,
+}
+
+impl<'writer> BranchWriter<'writer> {
+    pub fn new(repository: &'writer gb_repository::Repository) -> Self {
+        Self { repository }
+    }
+
+    pub fn write_selected(&self, id: &Option<String>) -> Result<()> {
+        let writer = writer::DirWriter::open(self.repository.root());
+        match id {
+            Some(id) => writer.write_string("branches/selected", id)?,
+            None => writer.remove("branches/selected")?,
+        }
+
+        Ok(())
+    }
+
+    /// Whether a branch record with `id` already exists under this writer's
+    /// repository, so callers can decide whether to overwrite it.
+    pub fn exists(&self, id: &str) -> bool {
+        self.repository.root().join(format!("branches/{}", id)).exists()
+    }
+
+    pub fn write(&self, branch: &Branch) -> Result<()> {
+        let writer = writer::DirWriter::open(self.repository.root());
+        let path = format!("branches/{}", branch.id);
+
+        writer.write_string(&format!("{}/id", path), &branch.id)?;
+        writer.write_string(&format!("{}/meta/name", path), &branch.name)?;
+        writer.write_bool(&format!("{}/meta/applied", path), branch.applied)?;
+        writer.write_string(&format!("{}/meta/upstream", path), &branch.upstream)?;
+        writer.write_string(&format!("{}/meta/tree", path), &branch.tree.to_string())?;
+        writer.write_string(&format!("{}/meta/head", path), &branch.head.to_string())?;
+        writer.write_u128(
+            &format!("{}/meta/created_timestamp_ms", path),
+            branch.created_timestamp_ms,
+        )?;
+        writer.write_u128(
+            &format!("{}/meta/updated_timestamp_ms", path),
+            branch.updated_timestamp_ms,
+        )?;
+        writer.write_string(
+            &format!("{}/meta/ownership", path),
+            &branch
+                .ownership
+                .iter()
+                .map(|ownership| ownership.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )?;
+
+        match &branch.signed_by {
+            Some(signed_by) => writer.write_string(&format!("{}/meta/signed_by", path), signed_by)?,
+            None => writer.remove(&format!("{}/meta/signed_by", path))?,
+        }
+
+        Ok(())
+    }
+
+    pub fn delete(&self, branch: &Branch) -> Result<()> {
+        let writer = writer::DirWriter::open(self.repository.root());
+        writer.remove(&format!("branches/{}", branch.id))?;
+
+        Ok(())
+    }
+}